@@ -0,0 +1,115 @@
+#!/bin/bash
+
+# Scroll through all points in the collection set in `COLLECTION` (default
+# `test`) and report points having a payload value type that doesn't match an
+# expected schema.
+#
+# The schema is a JSON file mapping payload field to expected type, one of:
+# `integer`, `float`, `string`, `bool`, `object` or `array`. Integers are
+# accepted where floats are expected. Nested fields are given with dots, such as
+# `meta.count`. Points not having a field are skipped, while explicit `null`
+# values and non-object parents of nested fields are reported.
+#
+# Limitation: jq parses `2` and `2.0` into the same number, so a whole number
+# stored as float, such as `2.0`, passes an `integer` check. Only floats with a
+# fractional part are reported where an integer is expected.
+#
+# Example schema:
+# $ echo '{"count": "integer", "name": "string"}' > schema.json
+#
+# Run test command:
+# $ ./check-payload-types.sh schema.json
+
+QDRANT_HOST="localhost:6333"
+COLLECTION=${COLLECTION:-test}
+BATCH=1000
+
+SCHEMA_FILE=$1
+if [[ -z $SCHEMA_FILE ]]; then
+    echo Usage: $0 SCHEMA_FILE >&2
+    exit 1
+fi
+SCHEMA=$(jq -c . "$SCHEMA_FILE") || exit 1
+INVALID=$(echo "$SCHEMA" | jq -r 'to_entries[] | select(.value | IN("integer", "float", "string", "bool", "object", "array") | not) | "\(.key): \(.value)"')
+if [[ -n $INVALID ]]; then
+    echo Schema has unknown types, expected one of integer, float, string, bool, object or array: >&2
+    echo "$INVALID" >&2
+    exit 1
+fi
+FIELDS=$(echo "$SCHEMA" | jq -c 'keys')
+
+OFFSET=null
+POINTS=0
+BAD_POINTS=0
+
+while true; do
+    PAGE=$(curl -L -X POST "http://$QDRANT_HOST/collections/$COLLECTION/points/scroll" \
+        -H 'Content-Type: application/json' \
+        --fail -s \
+        --data-raw "{
+            \"limit\": $BATCH,
+            \"offset\": $OFFSET,
+            \"with_payload\": $FIELDS,
+            \"with_vector\": false
+        }")
+    if [[ $? != 0 ]]; then
+        echo Failed to scroll $COLLECTION on $QDRANT_HOST at offset $OFFSET >&2
+        exit 1
+    fi
+
+    REPORT=$(echo "$PAGE" | jq -r --argjson schema "$SCHEMA" '
+        def value_type:
+            if type == "number" then
+                if . == floor then "integer" else "float" end
+            elif type == "boolean" then "bool"
+            else type
+            end;
+        def matches($expected):
+            value_type == $expected or ($expected == "float" and value_type == "integer");
+        # Resolve a dotted path, yields nothing if a key is absent and the
+        # offending value with its path if a parent is not an object
+        def resolve($path; $at):
+            if ($path | length) == 0 then
+                { "at": $at, "value": . }
+            elif type != "object" then
+                { "at": $at, "value": ., "parent": true }
+            elif has($path[0]) then
+                .[$path[0]] | resolve($path[1:]; $at + [$path[0]])
+            else
+                empty
+            end;
+
+        .result.points[]
+            | .id as $id
+            | (.payload // {}) as $payload
+            | $schema
+            | to_entries[]
+            | .key as $field
+            | .value as $expected
+            | $payload
+            | resolve($field | split("."); [])
+            | if .parent then
+                "point \($id): field \($field) expected \($expected), found \(.value | value_type) at \(.at | join(".")) (\(.value | tojson))"
+            elif .value | matches($expected) | not then
+                "point \($id): field \($field) expected \($expected), found \(.value | value_type) (\(.value | tojson))"
+            else
+                empty
+            end
+    ')
+    if [[ -n $REPORT ]]; then
+        echo "$REPORT"
+        BAD_POINTS=$((BAD_POINTS + $(echo "$REPORT" | cut -d: -f1 | sort -u | wc -l)))
+    fi
+
+    POINTS=$((POINTS + $(echo "$PAGE" | jq '.result.points | length')))
+    OFFSET=$(echo "$PAGE" | jq -c '.result.next_page_offset')
+    if [[ $OFFSET == null ]]; then
+        break
+    fi
+done
+
+echo Checked $POINTS points, $BAD_POINTS with mismatching payload types
+
+if [[ $BAD_POINTS -gt 0 ]]; then
+    exit 1
+fi
@@ -0,0 +1,44 @@
+#!/bin/bash
+
+# Continuously poll cluster info of the collection set in `COLLECTION` (default
+# `test`) and append a timestamped JSONL record to a timeline file.
+#
+# Each record holds the peer ID, local and remote shard states and ongoing shard
+# transfers. Cross reference it with inconsistency timestamps reported by other
+# tools to see what topology coincided with divergence.
+#
+# Run alongside a test, in the background:
+# $ ./cluster-timeline.sh timeline.jsonl &
+#
+# Poll every 0.2 seconds:
+# $ INTERVAL=0.2s ./cluster-timeline.sh timeline.jsonl
+
+QDRANT_HOST="localhost:6333"
+COLLECTION=${COLLECTION:-test}
+INTERVAL=${INTERVAL:-1s}
+OUTPUT=${1:-cluster-timeline.jsonl}
+
+echo Appending cluster timeline of $COLLECTION to $OUTPUT every $INTERVAL
+
+while true; do
+    TIME=$(date -u +%Y-%m-%dT%H:%M:%S.%3NZ)
+
+    CLUSTER=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
+        -H 'Content-Type: application/json' \
+        --fail -s)
+
+    if [[ $? != 0 ]]; then
+        echo "$TIME: failed to fetch cluster info" >&2
+        jq -cn --arg time "$TIME" '{ "time": $time, "error": "failed to fetch cluster info" }' >> "$OUTPUT"
+    else
+        echo "$CLUSTER" | jq -c --arg time "$TIME" '{
+            "time": $time,
+            "peer_id": .result.peer_id,
+            "local_shards": .result.local_shards,
+            "remote_shards": .result.remote_shards,
+            "shard_transfers": .result.shard_transfers,
+        }' >> "$OUTPUT"
+    fi
+
+    sleep $INTERVAL
+done
@@ -1,7 +1,8 @@
 #!/bin/bash
 
-# On a cluster, move shard from `test` collection to another peer using shard
-# snapshot transfer and abort it right after.
+# On a cluster, move shard from the collection set in `COLLECTION` (default
+# `test`) to another peer using shard snapshot transfer and abort it right
+# after.
 #
 # Start first node:
 # $ QDRANT__LOG_LEVEL=collection::shards=trace,actix_web=warn,debug QDRANT__CLUSTER__ENABLED=true mold -run cargo run -- --uri http://localhost:6335
@@ -12,39 +13,96 @@
 # Run test command:
 # $ bfb --collection-name test -n 10000 --indexing-threshold 0 --skip-wait-index && ./move-shard-snapshot.sh
 #
+# Move a specific shard instead of the first local one:
+# $ SHARD_ID=1 ./move-abort-shard-snapshot.sh
+#
 # Clean up after testing:
 # rm -r storage{,2}
 
 QDRANT_HOST="localhost:6333"
-COLLECTION=test
+COLLECTION=${COLLECTION:-test}
+SETTLE_TIMEOUT=60
 
 CLUSTER=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
   -H 'Content-Type: application/json' \
   --fail -s)
+if [[ $? != 0 ]]; then
+    echo Failed to fetch cluster info of $COLLECTION from $QDRANT_HOST >&2
+    exit 1
+fi
+
+# Shard to move, defaults to the first local shard
+SHARD_ID=${SHARD_ID:-$(echo $CLUSTER | jq '.result.local_shards[0].shard_id // empty')}
+if [[ -z $SHARD_ID ]]; then
+    echo No local shard of $COLLECTION on $QDRANT_HOST to move >&2
+    exit 1
+elif ! [[ $SHARD_ID =~ ^[0-9]+$ ]]; then
+    echo SHARD_ID must be a shard number, got: $SHARD_ID >&2
+    exit 1
+elif ! echo $CLUSTER | jq -e --argjson shard "$SHARD_ID" 'any(.result.local_shards[]; .shard_id == $shard)' > /dev/null; then
+    echo Shard $SHARD_ID does not exist on peer $(echo $CLUSTER | jq '.result.peer_id') >&2
+    exit 1
+fi
+
+# Peer to move to, the first cluster peer not already hosting the shard
+PEERS=$(curl -L -X GET "http://$QDRANT_HOST/cluster" \
+  -H 'Content-Type: application/json' \
+  --fail -s)
+if [[ $? != 0 ]]; then
+    echo Failed to fetch cluster peers from $QDRANT_HOST >&2
+    exit 1
+fi
+TARGET_PEER=$(jq -n --argjson cluster "$CLUSTER" --argjson peers "$PEERS" --argjson shard $SHARD_ID '
+    ([$cluster.result.peer_id] + [$cluster.result.remote_shards[] | select(.shard_id == $shard) | .peer_id]) as $holders
+        | [$peers.result.peers | keys | map(tonumber) | sort | .[] | select(IN($holders[]) | not)]
+        | first // empty')
+if [[ -z $TARGET_PEER ]]; then
+    echo No peer found that does not already host shard $SHARD_ID >&2
+    exit 1
+fi
 
 function abort {
     sleep 3s
 
-    ABORT=$(echo $CLUSTER | jq '{
+    ABORT=$(echo $CLUSTER | jq --argjson shard $SHARD_ID --argjson target $TARGET_PEER '{
         "abort_transfer": {
             "from_peer_id": .result.peer_id,
-            "shard_id": .result.local_shards[0].shard_id,
-            "to_peer_id": .result.remote_shards[0].peer_id,
+            "shard_id": $shard,
+            "to_peer_id": $target,
         }
     }')
     echo Requesting transfer abort: $ABORT
 
-    curl -X POST "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
+    RESPONSE=$(curl -X POST "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
         -H 'Content-Type: application/json' \
-        --fail -s \
-        --data-raw "$ABORT" | jq
+        -s -w '\n%{http_code}' \
+        --data-raw "$ABORT")
+    if [[ $? != 0 ]]; then
+        echo Failed to send transfer abort >&2
+        return 1
+    fi
+    echo "${RESPONSE%$'\n'*}" | jq
+    [[ ${RESPONSE##*$'\n'} == 200 ]]
 }
 
-MOVE=$(echo $CLUSTER | jq '{
+function wait_no_transfers {
+    for ((i=0; i<SETTLE_TIMEOUT*2; ++i)); do
+        count=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
+            -H 'Content-Type: application/json' \
+            --fail -s | jq '.result.shard_transfers | length')
+        if [[ $count == '0' ]]; then
+            return 0
+        fi
+        sleep 0.5s
+    done
+    return 1
+}
+
+MOVE=$(echo $CLUSTER | jq --argjson shard $SHARD_ID --argjson target $TARGET_PEER '{
     "move_shard": {
         "from_peer_id": .result.peer_id,
-        "shard_id": .result.local_shards[0].shard_id,
-        "to_peer_id": .result.remote_shards[0].peer_id,
+        "shard_id": $shard,
+        "to_peer_id": $target,
         "method": "snapshot"
     }
 }')
@@ -56,6 +114,7 @@ curl -X POST "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
   --data-raw "$MOVE" | jq
 
 abort &
+ABORT_PID=$!
 
 for i in {1..20};
 do
@@ -76,3 +135,19 @@ do
     ]}" | jq
     sleep 0.5
 done
+
+# Wait for the abort, then verify the cluster settles without any transfers
+wait $ABORT_PID
+ABORTED=$?
+echo Waiting for shard transfers to settle
+if wait_no_transfers; then
+    echo Cluster settled, no shard transfers left
+else
+    echo Shard transfers still present after ${SETTLE_TIMEOUT}s >&2
+    exit 1
+fi
+
+if [[ $ABORTED != 0 ]]; then
+    echo Transfer abort was rejected, the transfer probably completed before it could be aborted so the abort path was not exercised >&2
+    exit 1
+fi
@@ -1,7 +1,7 @@
 #!/bin/bash
 
-# On a cluster, in a loop, move shard from `test` collection to another peer using shard
-# snapshot transfer and back.
+# On a cluster, in a loop, move shard from the collection set in `COLLECTION`
+# (default `test`) to another peer using shard snapshot transfer and back.
 #
 # Start first node:
 # $ QDRANT__LOG_LEVEL=collection::shards=trace,actix_web=warn,debug QDRANT__CLUSTER__ENABLED=true mold -run cargo run -- --uri http://localhost:6335
@@ -12,28 +12,65 @@
 # Run test command:
 # $ bfb --collection-name test -n 10000 --indexing-threshold 0 --skip-wait-index && ./move-shard-snapshot-loop.sh
 #
+# Move a specific shard instead of the first local one:
+# $ SHARD_ID=1 ./move-shard-snapshot-loop.sh
+#
 # Clean up after testing:
 # rm -r storage{,2}
 
 QDRANT_HOST="localhost:6333"
-COLLECTION=test
+COLLECTION=${COLLECTION:-test}
 
 CLUSTER=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
   -H 'Content-Type: application/json' \
   --fail -s)
+if [[ $? != 0 ]]; then
+    echo Failed to fetch cluster info of $COLLECTION from $QDRANT_HOST >&2
+    exit 1
+fi
+
+# Shard to move, defaults to the first local shard
+SHARD_ID=${SHARD_ID:-$(echo $CLUSTER | jq '.result.local_shards[0].shard_id // empty')}
+if [[ -z $SHARD_ID ]]; then
+    echo No local shard of $COLLECTION on $QDRANT_HOST to move >&2
+    exit 1
+elif ! [[ $SHARD_ID =~ ^[0-9]+$ ]]; then
+    echo SHARD_ID must be a shard number, got: $SHARD_ID >&2
+    exit 1
+elif ! echo $CLUSTER | jq -e --argjson shard "$SHARD_ID" 'any(.result.local_shards[]; .shard_id == $shard)' > /dev/null; then
+    echo Shard $SHARD_ID does not exist on peer $(echo $CLUSTER | jq '.result.peer_id') >&2
+    exit 1
+fi
+
+# Peer to move to, the first cluster peer not already hosting the shard
+PEERS=$(curl -L -X GET "http://$QDRANT_HOST/cluster" \
+  -H 'Content-Type: application/json' \
+  --fail -s)
+if [[ $? != 0 ]]; then
+    echo Failed to fetch cluster peers from $QDRANT_HOST >&2
+    exit 1
+fi
+TARGET_PEER=$(jq -n --argjson cluster "$CLUSTER" --argjson peers "$PEERS" --argjson shard $SHARD_ID '
+    ([$cluster.result.peer_id] + [$cluster.result.remote_shards[] | select(.shard_id == $shard) | .peer_id]) as $holders
+        | [$peers.result.peers | keys | map(tonumber) | sort | .[] | select(IN($holders[]) | not)]
+        | first // empty')
+if [[ -z $TARGET_PEER ]]; then
+    echo No peer found that does not already host shard $SHARD_ID >&2
+    exit 1
+fi
 
-MOVE1=$(echo $CLUSTER | jq '{
+MOVE1=$(echo $CLUSTER | jq --argjson shard $SHARD_ID --argjson target $TARGET_PEER '{
     "move_shard": {
         "from_peer_id": .result.peer_id,
-        "shard_id": .result.local_shards[0].shard_id,
-        "to_peer_id": .result.remote_shards[0].peer_id,
+        "shard_id": $shard,
+        "to_peer_id": $target,
         "method": "snapshot"
     }
 }')
-MOVE2=$(echo $CLUSTER | jq '{
+MOVE2=$(echo $CLUSTER | jq --argjson shard $SHARD_ID --argjson target $TARGET_PEER '{
     "move_shard": {
-        "from_peer_id": .result.remote_shards[0].peer_id,
-        "shard_id": .result.local_shards[0].shard_id,
+        "from_peer_id": $target,
+        "shard_id": $shard,
         "to_peer_id": .result.peer_id,
         "method": "snapshot"
     }
@@ -80,15 +117,81 @@ function updates {
     done
 }
 
+# Stop background updates, abort running transfers and report progress on Ctrl-C
+function shutdown {
+    kill $UPDATES_PID 2> /dev/null
+    FAILED_ABORTS=()
+    echo
+
+    INFO=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
+        -H 'Content-Type: application/json' \
+        --fail -s)
+    if [[ $? != 0 ]] || ! TRANSFERS=$(echo "$INFO" | jq -c '.result.shard_transfers[]'); then
+        echo Failed to fetch cluster info, shard transfers may still be running >&2
+    elif [[ -z $TRANSFERS ]]; then
+        echo No shard transfers running
+    else
+        while read -r transfer; do
+            ABORT=$(echo "$transfer" | jq '{
+                "abort_transfer": {
+                    "from_peer_id": .from,
+                    "shard_id": .shard_id,
+                    "to_peer_id": .to,
+                }
+            }')
+            echo Requesting transfer abort: $ABORT
+            curl -X POST "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
+                -H 'Content-Type: application/json' \
+                --fail -s \
+                --data-raw "$ABORT" | jq
+            if [[ ${PIPESTATUS[0]} != 0 ]]; then
+                FAILED_ABORTS+=("$(echo "$transfer" | jq -r '"shard \(.shard_id) from peer \(.from) to \(.to)"')")
+            fi
+        done <<< "$TRANSFERS"
+
+        for failed in "${FAILED_ABORTS[@]}"; do
+            echo Failed to abort transfer of $failed >&2
+        done
+
+        sleep 1s
+        INFO=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
+            -H 'Content-Type: application/json' \
+            --fail -s)
+        if [[ $? != 0 ]] || ! REMAINING=$(echo "$INFO" | jq '.result.shard_transfers | length'); then
+            echo Failed to fetch cluster info, could not confirm transfers were aborted >&2
+        elif [[ $REMAINING == 0 ]]; then
+            echo All shard transfers aborted
+        else
+            echo $REMAINING shard transfers still present after aborting >&2
+        fi
+    fi
+
+    if [[ $LAST_SETTLED == true ]]; then
+        echo Stopped after $ROUNDS completed rounds, last round finished with no transfers left
+    else
+        echo Stopped after $ROUNDS completed rounds, last round was interrupted before its transfers finished
+    fi
+    exit $1
+}
+
+ROUNDS=0
+LAST_SETTLED=true
+trap 'shutdown 130' INT
+trap 'shutdown 143' TERM
+
 # Update points twice a second forever
 updates &
+UPDATES_PID=$!
 
 # Loop transfer forever
 while true; do
+    LAST_SETTLED=false
     move1
     sleep 1
     wait_no_transfers
     move2
     sleep 1
     wait_no_transfers
+    LAST_SETTLED=true
+    ROUNDS=$((ROUNDS + 1))
 done
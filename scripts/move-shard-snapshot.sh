@@ -1,7 +1,7 @@
 #!/bin/bash
 
-# On a cluster, move shard from `test` collection to another peer using shard
-# snapshot transfer.
+# On a cluster, move shard from the collection set in `COLLECTION` (default
+# `test`) to another peer using shard snapshot transfer.
 #
 # Start first node:
 # $ QDRANT__LOG_LEVEL=collection::shards=trace,actix_web=warn,debug QDRANT__CLUSTER__ENABLED=true mold -run cargo run -- --uri http://localhost:6335
@@ -12,21 +12,58 @@
 # Run test command:
 # $ bfb --collection-name test -n 10000 --indexing-threshold 0 --skip-wait-index && ./move-shard-snapshot.sh
 #
+# Move a specific shard instead of the first local one:
+# $ SHARD_ID=1 ./move-shard-snapshot.sh
+#
 # Clean up after testing:
 # rm -r storage{,2}
 
 QDRANT_HOST="localhost:6333"
-COLLECTION=test
+COLLECTION=${COLLECTION:-test}
 
 CLUSTER=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION/cluster" \
   -H 'Content-Type: application/json' \
   --fail -s)
+if [[ $? != 0 ]]; then
+    echo Failed to fetch cluster info of $COLLECTION from $QDRANT_HOST >&2
+    exit 1
+fi
+
+# Shard to move, defaults to the first local shard
+SHARD_ID=${SHARD_ID:-$(echo $CLUSTER | jq '.result.local_shards[0].shard_id // empty')}
+if [[ -z $SHARD_ID ]]; then
+    echo No local shard of $COLLECTION on $QDRANT_HOST to move >&2
+    exit 1
+elif ! [[ $SHARD_ID =~ ^[0-9]+$ ]]; then
+    echo SHARD_ID must be a shard number, got: $SHARD_ID >&2
+    exit 1
+elif ! echo $CLUSTER | jq -e --argjson shard "$SHARD_ID" 'any(.result.local_shards[]; .shard_id == $shard)' > /dev/null; then
+    echo Shard $SHARD_ID does not exist on peer $(echo $CLUSTER | jq '.result.peer_id') >&2
+    exit 1
+fi
+
+# Peer to move to, the first cluster peer not already hosting the shard
+PEERS=$(curl -L -X GET "http://$QDRANT_HOST/cluster" \
+  -H 'Content-Type: application/json' \
+  --fail -s)
+if [[ $? != 0 ]]; then
+    echo Failed to fetch cluster peers from $QDRANT_HOST >&2
+    exit 1
+fi
+TARGET_PEER=$(jq -n --argjson cluster "$CLUSTER" --argjson peers "$PEERS" --argjson shard $SHARD_ID '
+    ([$cluster.result.peer_id] + [$cluster.result.remote_shards[] | select(.shard_id == $shard) | .peer_id]) as $holders
+        | [$peers.result.peers | keys | map(tonumber) | sort | .[] | select(IN($holders[]) | not)]
+        | first // empty')
+if [[ -z $TARGET_PEER ]]; then
+    echo No peer found that does not already host shard $SHARD_ID >&2
+    exit 1
+fi
 
-MOVE=$(echo $CLUSTER | jq '{
+MOVE=$(echo $CLUSTER | jq --argjson shard $SHARD_ID --argjson target $TARGET_PEER '{
     "move_shard": {
         "from_peer_id": .result.peer_id,
-        "shard_id": .result.local_shards[0].shard_id,
-        "to_peer_id": .result.remote_shards[0].peer_id,
+        "shard_id": $shard,
+        "to_peer_id": $target,
         "method": "snapshot"
     }
 }')
@@ -0,0 +1,175 @@
+#!/bin/bash
+
+# Verify that vectors stored on disk round-trip correctly on every host.
+#
+# Drops and recreates the `on-disk-test` collection with on disk vectors
+# replicated to all hosts, upserts known vectors, triggers an optimizer run and
+# waits for it to finish. It then reads back all vectors from each host and
+# reports any point that differs from what was written by more than EPSILON.
+#
+# Vectors use the Dot distance so that they are stored as is, without
+# normalization.
+#
+# Start first node:
+# $ QDRANT__CLUSTER__ENABLED=true mold -run cargo run -- --uri http://localhost:6335
+#
+# Start second node:
+# $ QDRANT__CLUSTER__ENABLED=true QDRANT__SERVICE__HTTP_PORT=6343 QDRANT__SERVICE__GRPC_PORT=6344 QDRANT__CLUSTER__P2P__PORT=6345 QDRANT__STORAGE__STORAGE_PATH=./storage2 mold -run cargo run -- --bootstrap http://localhost:6335
+#
+# Run test command:
+# $ ./on-disk-roundtrip.sh
+#
+# Use a different collection, it is dropped as well:
+# $ ROUNDTRIP_COLLECTION=my-on-disk-test ./on-disk-roundtrip.sh
+#
+# Clean up after testing:
+# rm -r storage{,2}
+
+HOSTS=("localhost:6333" "localhost:6343")
+# Dropped and recreated on every run, deliberately not shared with other scripts
+COLLECTION=${ROUNDTRIP_COLLECTION:-on-disk-test}
+DIM=128
+POINT_COUNT=1000
+BATCH=100
+EPSILON=0.0001
+OPTIMIZE_TIMEOUT=60
+
+# Deterministic vector for a point ID, must stay the same for writing and checking
+VECTOR_FN='def vector($id): [range('$DIM') as $d | (($id * 31 + $d * 7) % 100) / 10];'
+
+function collection_info {
+    curl -L -X GET "http://$1/collections/$COLLECTION" \
+        -H 'Content-Type: application/json' \
+        --fail -s | jq -r "$2"
+}
+
+# Wait for optimizations to be picked up, the collection turning non-green or the
+# number of indexed vectors growing beyond the given count
+function wait_optimizing {
+    for ((i=0; i<OPTIMIZE_TIMEOUT*2; ++i)); do
+        info=$(collection_info $1 '"\(.result.status) \(.result.indexed_vectors_count)"')
+        read status indexed <<< "$info"
+        if [[ -n $status && $status != green ]] || [[ $indexed =~ ^[0-9]+$ && $indexed -gt $2 ]]; then
+            return 0
+        fi
+        sleep 0.5s
+    done
+    return 1
+}
+
+function wait_green {
+    for ((i=0; i<OPTIMIZE_TIMEOUT*2; ++i)); do
+        if [[ $(collection_info $1 '.result.status') == 'green' ]]; then
+            return 0
+        fi
+        sleep 0.5s
+    done
+    return 1
+}
+
+echo Recreating collection $COLLECTION
+curl -X DELETE "http://${HOSTS[0]}/collections/$COLLECTION" \
+    -H 'Content-Type: application/json' \
+    --fail -s | jq
+CREATE=$(curl -X PUT "http://${HOSTS[0]}/collections/$COLLECTION" \
+    -H 'Content-Type: application/json' \
+    --fail -s \
+    --data-raw "{
+        \"vectors\": {
+            \"size\": $DIM,
+            \"distance\": \"Dot\",
+            \"on_disk\": true
+        },
+        \"replication_factor\": ${#HOSTS[@]},
+        \"optimizers_config\": {
+            \"indexing_threshold\": 0
+        }
+    }")
+if [[ $? != 0 ]] || ! echo "$CREATE" | jq -e '.result == true' > /dev/null; then
+    echo Failed to create collection $COLLECTION, not testing against a collection with unknown config >&2
+    exit 1
+fi
+
+echo Upserting $POINT_COUNT points
+for ((start=0; start<POINT_COUNT; start+=BATCH)); do
+    POINTS=$(jq -cn --argjson from $start --argjson to $((start + BATCH)) "$VECTOR_FN"'{
+        "points": [range($from; $to) | { "id": ., "vector": vector(.) }]
+    }')
+    curl -L -X PUT "http://${HOSTS[0]}/collections/$COLLECTION/points?wait=true" \
+        -H 'Content-Type: application/json' \
+        --fail -s \
+        --data-raw "$POINTS" > /dev/null
+    if [[ $? != 0 ]]; then
+        echo Failed to upsert batch at $start, not checking for storage errors after a write failure >&2
+        exit 1
+    fi
+done
+
+declare -A INDEXED_BEFORE
+for host in "${HOSTS[@]}"; do
+    INDEXED_BEFORE[$host]=$(collection_info $host '.result.indexed_vectors_count // 0')
+done
+
+echo Triggering optimizer run
+curl -X PATCH "http://${HOSTS[0]}/collections/$COLLECTION" \
+    -H 'Content-Type: application/json' \
+    --fail -s \
+    --data-raw '{ "optimizers_config": { "indexing_threshold": 1 } }' | jq
+
+FAILED=0
+for host in "${HOSTS[@]}"; do
+    echo Waiting for optimizations to start on $host
+    if ! wait_optimizing $host ${INDEXED_BEFORE[$host]:-0}; then
+        echo Host $host: optimizations did not start within ${OPTIMIZE_TIMEOUT}s >&2
+        FAILED=1
+        continue
+    fi
+
+    echo Waiting for optimizations to finish on $host
+    if ! wait_green $host; then
+        echo Host $host: collection did not become green within ${OPTIMIZE_TIMEOUT}s >&2
+        FAILED=1
+        continue
+    fi
+
+    IDS=$(jq -cn --argjson count $POINT_COUNT '[range($count)]')
+    RESPONSE=$(curl -L -X POST "http://$host/collections/$COLLECTION/points" \
+        -H 'Content-Type: application/json' \
+        --fail -s \
+        --data-raw "{ \"ids\": $IDS, \"with_vector\": true, \"with_payload\": false }")
+    if [[ $? != 0 ]] || ! echo "$RESPONSE" | jq -e '.result | type == "array"' > /dev/null; then
+        echo Host $host: failed to read back vectors >&2
+        FAILED=1
+        continue
+    fi
+
+    MISMATCHES=$(echo "$RESPONSE" | jq -r --argjson count $POINT_COUNT --argjson epsilon $EPSILON "$VECTOR_FN"'
+            (.result | map({ (.id | tostring): .vector }) | add // {}) as $read
+            | range($count)
+            | . as $id
+            | vector($id) as $expected
+            | $read[$id | tostring]
+            | if . == null then
+                "point \($id): missing"
+            elif length != ($expected | length) or ([., $expected] | transpose | any(.[0] - .[1] | fabs > $epsilon)) then
+                "point \($id): vector diverges from what was written"
+            else
+                empty
+            end
+        ')
+    if [[ $? != 0 ]]; then
+        echo Host $host: failed to compare vectors >&2
+        FAILED=1
+        continue
+    fi
+
+    if [[ -n $MISMATCHES ]]; then
+        echo "$MISMATCHES"
+        echo Host $host: $(echo "$MISMATCHES" | wc -l) points diverge
+        FAILED=1
+    else
+        echo Host $host: all $POINT_COUNT vectors round-tripped correctly
+    fi
+done
+
+exit $FAILED
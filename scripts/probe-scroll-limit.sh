@@ -0,0 +1,64 @@
+#!/bin/bash
+
+# Probe the effective scroll limit of a host on the collection set in
+# `COLLECTION` (default `test`).
+#
+# Requests a single scroll page with a huge limit and compares the number of
+# returned points with the exact point count. If fewer points are returned while
+# there is a next page, the server capped the limit and tools relying on a
+# single huge scroll silently miss points.
+#
+# Run test command:
+# $ ./probe-scroll-limit.sh
+#
+# Probe with a specific limit:
+# $ LIMIT=100000 ./probe-scroll-limit.sh
+
+QDRANT_HOST="localhost:6333"
+COLLECTION=${COLLECTION:-test}
+LIMIT=${LIMIT:-4294967295}
+
+COUNT=$(curl -L -X POST "http://$QDRANT_HOST/collections/$COLLECTION/points/count" \
+    -H 'Content-Type: application/json' \
+    --fail -s \
+    --data-raw '{ "exact": true }' | jq '.result.count')
+if [[ -z $COUNT ]]; then
+    echo Failed to count points >&2
+    exit 1
+fi
+
+RESPONSE=$(curl -L -X POST "http://$QDRANT_HOST/collections/$COLLECTION/points/scroll" \
+    -H 'Content-Type: application/json' \
+    -s -w '\n%{http_code}' \
+    --data-raw "{
+        \"limit\": $LIMIT,
+        \"with_payload\": false,
+        \"with_vector\": false
+    }")
+if [[ $? != 0 ]]; then
+    echo Failed to send scroll request to $QDRANT_HOST >&2
+    exit 1
+fi
+STATUS=${RESPONSE##*$'\n'}
+PAGE=${RESPONSE%$'\n'*}
+if [[ $STATUS != 200 ]]; then
+    echo Server rejected scroll with limit $LIMIT, HTTP $STATUS: $(echo "$PAGE" | jq -r '.status.error // .' 2> /dev/null || echo "$PAGE") >&2
+    exit 1
+fi
+
+RETURNED=$(echo "$PAGE" | jq '.result.points | length')
+NEXT=$(echo "$PAGE" | jq -c '.result.next_page_offset')
+
+echo Collection has $COUNT points, scroll with limit $LIMIT returned $RETURNED points, next page offset: $NEXT
+
+if [[ $NEXT != null && $RETURNED -lt $LIMIT ]]; then
+    echo Scroll limit is capped at $RETURNED points
+    exit 1
+elif [[ $NEXT != null ]]; then
+    echo Scroll limit of $LIMIT is honoured, remaining points are on the next page
+elif [[ $RETURNED -lt $COUNT ]]; then
+    echo Scroll returned fewer points than counted without a next page, are points being written concurrently?
+    exit 1
+else
+    echo Scroll limit is not capped for this collection size
+fi
@@ -0,0 +1,51 @@
+#!/bin/bash
+
+# Wait for the collection set in `COLLECTION` (default `test`) to become green,
+# reporting optimization progress while waiting.
+#
+# Prints the collection status, optimizer status, segment count and the number
+# of indexed versus total points on every poll. Helps to tell whether a yellow
+# collection is still optimizing rather than diverged. Exits with code 2 if the
+# collection is grey, because its pending optimizations won't start by themselves.
+#
+# Run test command:
+# $ ./wait-green.sh
+#
+# Give up after 5 minutes:
+# $ TIMEOUT=300 ./wait-green.sh
+
+QDRANT_HOST="localhost:6333"
+COLLECTION=${COLLECTION:-test}
+INTERVAL=${INTERVAL:-1s}
+TIMEOUT=${TIMEOUT:-0}
+
+START=$SECONDS
+
+while true; do
+    INFO=$(curl -L -X GET "http://$QDRANT_HOST/collections/$COLLECTION" \
+        -H 'Content-Type: application/json' \
+        --fail -s)
+
+    if [[ $? != 0 ]]; then
+        echo Failed to fetch collection info >&2
+    else
+        echo "$INFO" | jq -r --arg time "$(date +%H:%M:%S)" '.result | "\($time) status: \(.status), optimizer: \(.optimizer_status | if type == "object" then "error: \(.error)" else . end), segments: \(.segments_count), points: \(.points_count), indexed vectors: \(.indexed_vectors_count)"'
+
+        STATUS=$(echo "$INFO" | jq -r '.result.status')
+        if [[ $STATUS == 'green' ]]; then
+            echo Collection $COLLECTION is green after $((SECONDS - START))s
+            exit 0
+        elif [[ $STATUS == 'grey' ]]; then
+            echo Collection $COLLECTION is grey, optimizations are pending and will not start until triggered by an update >&2
+            echo Trigger them with: curl -X PATCH http://$QDRANT_HOST/collections/$COLLECTION -H \'Content-Type: application/json\' --data-raw \''{ "optimizers_config": {} }'\' >&2
+            exit 2
+        fi
+    fi
+
+    if [[ $TIMEOUT != 0 && $((SECONDS - START)) -ge $TIMEOUT ]]; then
+        echo Collection $COLLECTION did not become green within ${TIMEOUT}s >&2
+        exit 1
+    fi
+
+    sleep $INTERVAL
+done